@@ -8,23 +8,62 @@
 // by the Apache License, Version 2.0.
 
 use crate::coord::{Coordinator, Message};
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use mz_audit_log::SchedulingDecisionsWithReasonsV1;
 use mz_catalog::memory::objects::{CatalogItem, ClusterVariant, ClusterVariantManaged};
 use mz_controller_types::ClusterId;
-use mz_ore::collections::CollectionExt;
 use mz_ore::soft_panic_or_log;
 use mz_repr::adt::interval::Interval;
-use mz_repr::GlobalId;
+use mz_repr::{GlobalId, Timestamp};
 use mz_sql::catalog::CatalogCluster;
 use mz_sql::plan::ClusterSchedule;
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant};
+use timely::progress::Antichain;
 use tracing::{debug, warn};
 
-const POLICIES: &[&str] = &[REFRESH_POLICY_NAME];
+/// The smallest REFRESH materialized view write frontiers per cluster, as collected from the
+/// catalog and storage controller. Paired with each cluster's REHYDRATION TIME ESTIMATE so that the
+/// On/Off decision can be computed once the oracle read ts is known.
+type RefreshMvWriteFrontiers = Vec<(ClusterId, Duration, Vec<(GlobalId, Antichain<Timestamp>)>)>;
 
 const REFRESH_POLICY_NAME: &str = "refresh";
 
+const IDLE_POLICY_NAME: &str = "idle";
+
+const WINDOW_POLICY_NAME: &str = "window";
+
+const DEPENDENCY_POLICY_NAME: &str = "dependency";
+
+/// How one managed cluster's On/Off decision depends on another cluster's.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DependencyRelation {
+    /// Be On when the target cluster is On.
+    Colocate,
+    /// Be Off when the target cluster is On.
+    AntiColocate,
+}
+
+impl DependencyRelation {
+    /// Applies the relation to the target cluster's On state to get this cluster's On state.
+    fn apply(&self, target_on: bool) -> bool {
+        match self {
+            DependencyRelation::Colocate => target_on,
+            DependencyRelation::AntiColocate => !target_on,
+        }
+    }
+}
+
+impl std::fmt::Display for DependencyRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DependencyRelation::Colocate => f.write_str("colocate"),
+            DependencyRelation::AntiColocate => f.write_str("anti-colocate"),
+        }
+    }
+}
+
 /// A policy's decision for whether it wants a certain cluster to be On, along with its reason.
 /// (Among the reasons there can be settings of the policy as well as other information about the
 /// state of the system.)
@@ -32,13 +71,57 @@ const REFRESH_POLICY_NAME: &str = "refresh";
 pub enum SchedulingDecision {
     /// The reason for the refresh policy for wanting to turn a cluster On or Off.
     Refresh(RefreshDecision),
+    /// The reason for the idle policy for wanting to turn a cluster On or Off.
+    Idle(IdleDecision),
+    /// The reason for the window policy for wanting to turn a cluster On or Off.
+    Window(WindowDecision),
+    /// The reason for the dependency policy for wanting to turn a cluster On or Off.
+    Dependency(DependencyDecision),
 }
 
 impl SchedulingDecision {
     /// Extract the On/Off decision from the policy-specific structs.
     pub fn cluster_on(&self) -> bool {
+        self.desired_replication_factor() > 0
+    }
+
+    /// The replication factor that this policy would like the cluster to have. `0` means the policy
+    /// wants the cluster Off; a positive number is a request for at least that many replicas. Today
+    /// a cluster carries a single `ClusterSchedule` variant, so exactly one policy ever opines about
+    /// it and every policy here returns only `0` or `1`; the `u32` return type leaves room for a
+    /// future schedule model that carries multiple policies and/or a load-aware policy that asks for
+    /// more replicas (e.g. while a large backfill is in flight), at which point
+    /// `handle_scheduling_decisions` would combine opinions by taking their maximum.
+    pub fn desired_replication_factor(&self) -> u32 {
         match &self {
-            SchedulingDecision::Refresh(RefreshDecision { cluster_on, .. }) => cluster_on.clone(),
+            SchedulingDecision::Refresh(RefreshDecision { cluster_on, .. }) => {
+                if *cluster_on {
+                    1
+                } else {
+                    0
+                }
+            }
+            SchedulingDecision::Idle(IdleDecision { cluster_on, .. }) => {
+                if *cluster_on {
+                    1
+                } else {
+                    0
+                }
+            }
+            SchedulingDecision::Window(WindowDecision { cluster_on, .. }) => {
+                if *cluster_on {
+                    1
+                } else {
+                    0
+                }
+            }
+            SchedulingDecision::Dependency(DependencyDecision { cluster_on, .. }) => {
+                if *cluster_on {
+                    1
+                } else {
+                    0
+                }
+            }
         }
     }
 }
@@ -54,14 +137,71 @@ pub struct RefreshDecision {
     rehydration_time_estimate: Duration,
 }
 
+#[derive(Clone, Debug)]
+pub struct IdleDecision {
+    /// Whether the ON IDLE policy wants a certain cluster to be On. This is true iff the cluster has
+    /// seen activity more recently than `suspend_after` ago, i.e. `now - last_activity <
+    /// suspend_after`.
+    cluster_on: bool,
+    /// When the cluster last saw activity (a peek, subscribe, index/MV installation, or DDL). If the
+    /// cluster has not seen any activity since envd started, this is the time that scheduling first
+    /// observed the cluster (or, from `explain_scheduling_decisions` before the policy first ran,
+    /// the time of that call).
+    last_activity: Instant,
+    /// The SUSPEND AFTER setting of the cluster, i.e., how long a cluster may stay idle before the
+    /// policy wants to turn it Off.
+    suspend_after: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct WindowDecision {
+    /// Whether the ON SCHEDULE policy wants a certain cluster to be On, i.e. whether the current
+    /// time (the local oracle read ts) falls inside one of the cluster's declared windows.
+    cluster_on: bool,
+    /// A human-readable description of the window that is currently active, if any.
+    active_window: Option<String>,
+    /// The next wall-clock instant at which this policy's decision is expected to change (the end of
+    /// the active window, or the start of the next one), if known.
+    next_transition: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DependencyDecision {
+    /// Whether the dependency policy wants this cluster to be On, after applying `relation` to the
+    /// target cluster's On state.
+    cluster_on: bool,
+    /// The cluster this one's decision depends on.
+    depends_on: ClusterId,
+    /// Whether this cluster should colocate with or anti-colocate from the target.
+    relation: DependencyRelation,
+}
+
+/// The would-be scheduling outcome for a single cluster, as computed by
+/// [`Coordinator::explain_scheduling_decisions`] without acting on it. This is the analog of a
+/// scheduler "simulate" mode: it answers "why is this cluster On/Off, and what would each policy
+/// decide right now?".
+#[derive(Clone, Debug)]
+pub struct ExplainSchedulingDecision {
+    /// The cluster these decisions are about.
+    pub cluster_id: ClusterId,
+    /// The decision of the policy that applies to this cluster's schedule, with its reasons.
+    pub decisions: Vec<SchedulingDecision>,
+    /// The replication factor the cluster would be set to, with `0` meaning Off, as resolved by
+    /// `resolve_desired_replication_factors`, the same step `handle_scheduling_decisions` uses.
+    pub desired_replication_factor: u32,
+}
+
 impl SchedulingDecision {
     pub fn reasons_to_audit_log_reasons<'a, I>(reasons: I) -> SchedulingDecisionsWithReasonsV1
     where
         I: IntoIterator<Item = &'a SchedulingDecision>,
     {
+        // Materialize once so we can scan it per policy below (the iterator is consumed otherwise).
+        let reasons = reasons.into_iter().collect::<Vec<_>>();
         SchedulingDecisionsWithReasonsV1 {
             on_refresh: reasons
-                .into_iter()
+                .iter()
+                .copied()
                 .filter_map(|r| match r {
                     SchedulingDecision::Refresh(RefreshDecision {
                         cluster_on,
@@ -84,8 +224,74 @@ impl SchedulingDecision {
                             rehydration_time_estimate: rehydration_time_estimate_str,
                         })
                     }
+                    _ => None,
+                })
+                .next(), // A single-variant schedule yields at most one decision of this policy type.
+            on_idle: reasons
+                .iter()
+                .copied()
+                .filter_map(|r| match r {
+                    SchedulingDecision::Idle(IdleDecision {
+                        cluster_on,
+                        last_activity,
+                        suspend_after,
+                    }) => {
+                        let mut suspend_after_str = String::new();
+                        mz_repr::strconv::format_interval(
+                            &mut suspend_after_str,
+                            Interval::from_duration(suspend_after).expect(
+                                "planning ensured that this is convertible back to Interval",
+                            ),
+                        );
+                        let mut idle_for_str = String::new();
+                        mz_repr::strconv::format_interval(
+                            &mut idle_for_str,
+                            Interval::from_duration(&last_activity.elapsed())
+                                .unwrap_or(Interval::default()),
+                        );
+                        Some(mz_audit_log::IdleDecisionWithReasonV1 {
+                            decision: (*cluster_on).into(),
+                            idle_for: idle_for_str,
+                            suspend_after: suspend_after_str,
+                        })
+                    }
+                    _ => None,
                 })
-                .into_element(), // Each policy should have exactly one opinion on each cluster.
+                .next(), // A single-variant schedule yields at most one decision of this policy type.
+            on_window: reasons
+                .iter()
+                .copied()
+                .filter_map(|r| match r {
+                    SchedulingDecision::Window(WindowDecision {
+                        cluster_on,
+                        active_window,
+                        next_transition,
+                    }) => Some(mz_audit_log::WindowDecisionWithReasonV1 {
+                        decision: (*cluster_on).into(),
+                        active_window: active_window.clone().unwrap_or_default(),
+                        next_transition: next_transition
+                            .map(|t| t.to_rfc3339())
+                            .unwrap_or_default(),
+                    }),
+                    _ => None,
+                })
+                .next(), // A single-variant schedule yields at most one decision of this policy type.
+            on_dependency: reasons
+                .iter()
+                .copied()
+                .filter_map(|r| match r {
+                    SchedulingDecision::Dependency(DependencyDecision {
+                        cluster_on,
+                        depends_on,
+                        relation,
+                    }) => Some(mz_audit_log::DependencyDecisionWithReasonV1 {
+                        decision: (*cluster_on).into(),
+                        depends_on: depends_on.to_string(),
+                        relation: relation.to_string(),
+                    }),
+                    _ => None,
+                })
+                .next(), // A single-variant schedule yields at most one decision of this policy type.
         }
     }
 }
@@ -94,8 +300,128 @@ impl Coordinator {
     #[mz_ore::instrument(level = "debug")]
     /// Call each scheduling policy.
     pub(crate) async fn check_scheduling_policies(&mut self) {
-        // (So far, we have only this one policy.)
         self.check_refresh_policy();
+        self.check_idle_policy();
+        self.check_window_policy();
+        self.check_dependency_policy();
+    }
+
+    /// Runs the `SCHEDULE = ON DEPENDENCY` cluster scheduling policy, which makes a cluster's On/Off
+    /// decision depend on another cluster's: `Colocate` wants this cluster On while the target is
+    /// On, `AntiColocate` wants it Off while the target is On. This emits a provisional decision
+    /// based on the target's *current* effective On state (`replication_factor > 0`); the final
+    /// decision is resolved against the target's freshly-decided state in a topological pass in
+    /// `handle_scheduling_decisions`, which is where cycles are detected.
+    fn check_dependency_policy(&mut self) {
+        let start_time = Instant::now();
+
+        let mut decisions = Vec::new();
+        for cluster in self.catalog().clusters() {
+            if let ClusterVariant::Managed(ref config) = cluster.config.variant {
+                if let ClusterSchedule::Dependency {
+                    depends_on,
+                    relation,
+                } = config.schedule
+                {
+                    let target_on = self
+                        .get_managed_cluster_config(depends_on)
+                        .map(|config| config.replication_factor > 0)
+                        .unwrap_or(false);
+                    let cluster_on = relation.apply(target_on);
+                    debug!(%cluster.id, %depends_on, %relation, target_on, cluster_on, "check_dependency_policy");
+                    decisions.push((
+                        cluster.id,
+                        SchedulingDecision::Dependency(DependencyDecision {
+                            cluster_on,
+                            depends_on,
+                            relation,
+                        }),
+                    ));
+                }
+            }
+        }
+
+        if let Err(e) = self
+            .internal_cmd_tx
+            .send(Message::SchedulingDecisions(vec![(
+                DEPENDENCY_POLICY_NAME,
+                decisions,
+            )]))
+        {
+            // It is not an error for this to run after `internal_cmd_rx` is dropped.
+            warn!("internal_cmd_rx dropped before we could send: {:?}", e);
+        }
+
+        self.metrics
+            .check_scheduling_policies_seconds
+            .with_label_values(&[DEPENDENCY_POLICY_NAME, "main"])
+            .observe((Instant::now() - start_time).as_secs_f64());
+    }
+
+    /// Records that `cluster_id` just saw activity (a peek, subscribe, index/MV installation, or
+    /// DDL). The `ON IDLE` policy keeps a cluster On for as long as `SUSPEND AFTER` has not elapsed
+    /// since the most recent such event.
+    ///
+    /// This must be called from every path that issues work against a cluster, otherwise the policy
+    /// only ever sees the "first observed" timestamp seeded in [`Self::check_idle_policy`] and
+    /// suspends the cluster `SUSPEND AFTER` later, never to bring it back. The call sites are:
+    /// `sequence_peek`/`sequence_copy_to` (the compute cluster a SELECT/COPY targets),
+    /// `sequence_subscribe` (the subscribe's cluster), `sequence_create_index` /
+    /// `sequence_create_materialized_view` (the cluster the object is installed on), and the managed
+    /// DDL sequencing paths in `sequence_alter_*` (the altered cluster).
+    pub(crate) fn bump_cluster_activity(&mut self, cluster_id: ClusterId) {
+        self.cluster_last_activity.insert(cluster_id, Instant::now());
+    }
+
+    /// Runs the `SCHEDULE = ON IDLE` cluster scheduling policy, which keeps a cluster On while it
+    /// has seen activity more recently than its `SUSPEND AFTER` interval, and wants it Off once it
+    /// has been idle for at least that long. Activity timestamps are maintained by
+    /// [`Self::bump_cluster_activity`], which is called whenever a peek, subscribe, index/MV
+    /// installation, or DDL executes against a cluster.
+    fn check_idle_policy(&mut self) {
+        let start_time = Instant::now();
+
+        let now = Instant::now();
+        let mut decisions = Vec::new();
+        for cluster in self.catalog().clusters() {
+            if let ClusterVariant::Managed(ref config) = cluster.config.variant {
+                if let ClusterSchedule::Idle { suspend_after } = config.schedule {
+                    // If we have never seen activity for this cluster, seed the timestamp with the
+                    // current time so that a freshly observed cluster is given a full `suspend_after`
+                    // window before we consider turning it off.
+                    let last_activity = *self
+                        .cluster_last_activity
+                        .entry(cluster.id)
+                        .or_insert_with(|| now);
+                    let cluster_on = now.saturating_duration_since(last_activity) < suspend_after;
+                    debug!(%cluster.id, ?last_activity, ?suspend_after, cluster_on, "check_idle_policy");
+                    decisions.push((
+                        cluster.id,
+                        SchedulingDecision::Idle(IdleDecision {
+                            cluster_on,
+                            last_activity,
+                            suspend_after,
+                        }),
+                    ));
+                }
+            }
+        }
+
+        if let Err(e) = self
+            .internal_cmd_tx
+            .send(Message::SchedulingDecisions(vec![(
+                IDLE_POLICY_NAME,
+                decisions,
+            )]))
+        {
+            // It is not an error for this to run after `internal_cmd_rx` is dropped.
+            warn!("internal_cmd_rx dropped before we could send: {:?}", e);
+        }
+
+        self.metrics
+            .check_scheduling_policies_seconds
+            .with_label_values(&[IDLE_POLICY_NAME, "main"])
+            .observe((Instant::now() - start_time).as_secs_f64());
     }
 
     /// Runs the `SCHEDULE = ON REFRESH` cluster scheduling policy, which makes cluster On/Off
@@ -106,13 +432,55 @@ impl Coordinator {
         let start_time = Instant::now();
 
         // Collect the smallest REFRESH MV write frontiers per cluster.
+        let refresh_mv_write_frontiers = self.collect_refresh_mv_write_frontiers(None);
+
+        // Spawn a background task that queries the timestamp oracle for the current read timestamp,
+        // compares this ts with the REFRESH MV write frontiers, thus making On/Off decisions per
+        // cluster, and sends a `Message::SchedulingDecisions` with these decisions.
+        let ts_oracle = self.get_local_timestamp_oracle();
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        let check_scheduling_policies_seconds_cloned =
+            self.metrics.check_scheduling_policies_seconds.clone();
+        mz_ore::task::spawn(|| "refresh policy get ts and make decisions", async move {
+            let task_start_time = Instant::now();
+            let local_read_ts = ts_oracle.read_ts().await;
+            debug!(%local_read_ts, ?refresh_mv_write_frontiers, "check_refresh_policy background task");
+            let decisions =
+                Self::refresh_decisions(refresh_mv_write_frontiers, local_read_ts);
+            if let Err(e) = internal_cmd_tx.send(Message::SchedulingDecisions(vec![(
+                REFRESH_POLICY_NAME,
+                decisions,
+            )])) {
+                // It is not an error for this task to be running after `internal_cmd_rx` is dropped.
+                warn!("internal_cmd_rx dropped before we could send: {:?}", e);
+            }
+            check_scheduling_policies_seconds_cloned
+                .with_label_values(&[REFRESH_POLICY_NAME, "background"])
+                .observe((Instant::now() - task_start_time).as_secs_f64());
+        });
+
+        self.metrics
+            .check_scheduling_policies_seconds
+            .with_label_values(&[REFRESH_POLICY_NAME, "main"])
+            .observe((Instant::now() - start_time).as_secs_f64());
+    }
+
+    /// Collects the smallest REFRESH materialized view write frontiers per `ON REFRESH` cluster from
+    /// the catalog and storage controller. If `cluster_filter` is `Some`, only that cluster is
+    /// considered; otherwise all managed `ON REFRESH` clusters are. Queries no external services, so
+    /// it can be called synchronously on the main loop (it is shared by `check_refresh_policy` and
+    /// `explain_scheduling_decisions`).
+    fn collect_refresh_mv_write_frontiers(
+        &self,
+        cluster_filter: Option<ClusterId>,
+    ) -> RefreshMvWriteFrontiers {
         let mut refresh_mv_write_frontiers = Vec::new();
         for cluster in self.catalog().clusters() {
+            if cluster_filter.is_some_and(|filter| filter != cluster.id) {
+                continue;
+            }
             if let ClusterVariant::Managed(ref config) = cluster.config.variant {
                 match config.schedule {
-                    ClusterSchedule::Manual => {
-                        // Nothing to do, user manages this cluster manually.
-                    }
                     ClusterSchedule::Refresh {
                         rehydration_time_estimate,
                     } => {
@@ -138,85 +506,268 @@ impl Coordinator {
                                 }
                             })
                             .collect_vec();
-                        debug!(%cluster.id, ?refresh_mv_write_frontiers, "check_refresh_policy");
+                        debug!(%cluster.id, ?refresh_mv_write_frontiers, "collect_refresh_mv_write_frontiers");
                         refresh_mv_write_frontiers.push((
                             cluster.id,
                             rehydration_time_estimate,
                             mvs,
                         ));
                     }
+                    _ => {
+                        // Not an `ON REFRESH` cluster; other policies handle it (or it is manual).
+                    }
+                }
+            }
+        }
+        refresh_mv_write_frontiers
+    }
+
+    /// Turns collected REFRESH MV write frontiers into per-cluster `SchedulingDecision`s given the
+    /// local oracle read ts. A cluster wants to be On iff any of its REFRESH MVs still needs a
+    /// refresh, i.e. its write frontier is behind `local_read_ts + rehydration_time_estimate`.
+    fn refresh_decisions(
+        refresh_mv_write_frontiers: RefreshMvWriteFrontiers,
+        local_read_ts: Timestamp,
+    ) -> Vec<(ClusterId, SchedulingDecision)> {
+        refresh_mv_write_frontiers
+            .into_iter()
+            .map(
+                |(cluster_id, rehydration_time_estimate, refresh_mv_write_frontiers)| {
+                    // We are just checking that
+                    // write_frontier < local_read_ts + rehydration_time_estimate
+                    let rehydration_estimate = &rehydration_time_estimate
+                        .try_into()
+                        .expect("checked during planning");
+                    let local_read_ts_adjusted = local_read_ts.step_forward_by(rehydration_estimate);
+                    let mvs_needing_refresh = refresh_mv_write_frontiers
+                        .into_iter()
+                        .filter_map(|(id, frontier)| {
+                            if frontier.less_than(&local_read_ts_adjusted) {
+                                Some(id)
+                            } else {
+                                None
+                            }
+                        })
+                        .collect_vec();
+                    let cluster_on = !mvs_needing_refresh.is_empty();
+                    (
+                        cluster_id,
+                        SchedulingDecision::Refresh(RefreshDecision {
+                            cluster_on,
+                            objects_needing_refresh: mvs_needing_refresh,
+                            rehydration_time_estimate,
+                        }),
+                    )
+                },
+            )
+            .collect()
+    }
+
+    /// Runs the `SCHEDULE = ON SCHEDULE` cluster scheduling policy, which keeps a cluster On only
+    /// during its declared wall-clock windows (e.g. business hours or a nightly batch window), and
+    /// sends `Message::SchedulingDecisions` with these decisions.
+    ///
+    /// Like the refresh policy, this does not read the machine wall clock directly: it reuses the
+    /// local timestamp oracle read ts (converted to a wall-clock instant) so that scheduling is
+    /// consistent across envd restarts and replicas. The oracle is queried on a background task.
+    fn check_window_policy(&mut self) {
+        let start_time = Instant::now();
+
+        // Collect the window specs per cluster.
+        let mut cluster_windows = Vec::new();
+        for cluster in self.catalog().clusters() {
+            if let ClusterVariant::Managed(ref config) = cluster.config.variant {
+                if let ClusterSchedule::Window { ref window } = config.schedule {
+                    cluster_windows.push((cluster.id, window.clone()));
                 }
             }
         }
 
         // Spawn a background task that queries the timestamp oracle for the current read timestamp,
-        // compares this ts with the REFRESH MV write frontiers, thus making On/Off decisions per
-        // cluster, and sends a `Message::SchedulingDecisions` with these decisions.
+        // converts it to a wall-clock instant, evaluates each cluster's windows against it, and
+        // sends a `Message::SchedulingDecisions` with these decisions.
         let ts_oracle = self.get_local_timestamp_oracle();
         let internal_cmd_tx = self.internal_cmd_tx.clone();
         let check_scheduling_policies_seconds_cloned =
             self.metrics.check_scheduling_policies_seconds.clone();
-        mz_ore::task::spawn(|| "refresh policy get ts and make decisions", async move {
+        mz_ore::task::spawn(|| "window policy get ts and make decisions", async move {
             let task_start_time = Instant::now();
             let local_read_ts = ts_oracle.read_ts().await;
-            debug!(%local_read_ts, ?refresh_mv_write_frontiers, "check_refresh_policy background task");
-            let decisions = refresh_mv_write_frontiers
+            let now = mz_ore::now::to_datetime(local_read_ts.into());
+            debug!(%local_read_ts, %now, "check_window_policy background task");
+            let decisions = cluster_windows
                 .into_iter()
-                .map(
-                    |(cluster_id, rehydration_time_estimate, refresh_mv_write_frontiers)| {
-                        // We are just checking that
-                        // write_frontier < local_read_ts + rehydration_time_estimate
-                        let rehydration_estimate = &rehydration_time_estimate
-                            .try_into()
-                            .expect("checked during planning");
-                        let local_read_ts_adjusted =
-                            local_read_ts.step_forward_by(rehydration_estimate);
-                        let mvs_needing_refresh = refresh_mv_write_frontiers
-                            .into_iter()
-                            .filter_map(|(id, frontier)| {
-                                if frontier.less_than(&local_read_ts_adjusted) {
-                                    Some(id)
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect_vec();
-                        let cluster_on = !mvs_needing_refresh.is_empty();
-                        (
-                            cluster_id,
-                            SchedulingDecision::Refresh(RefreshDecision {
-                                cluster_on,
-                                objects_needing_refresh: mvs_needing_refresh,
-                                rehydration_time_estimate,
-                            }),
-                        )
-                    },
-                )
+                .map(|(cluster_id, window)| {
+                    let (cluster_on, active_window, next_transition) = window.evaluate(now);
+                    (
+                        cluster_id,
+                        SchedulingDecision::Window(WindowDecision {
+                            cluster_on,
+                            active_window,
+                            next_transition,
+                        }),
+                    )
+                })
                 .collect();
             if let Err(e) = internal_cmd_tx.send(Message::SchedulingDecisions(vec![(
-                REFRESH_POLICY_NAME,
+                WINDOW_POLICY_NAME,
                 decisions,
             )])) {
                 // It is not an error for this task to be running after `internal_cmd_rx` is dropped.
                 warn!("internal_cmd_rx dropped before we could send: {:?}", e);
             }
             check_scheduling_policies_seconds_cloned
-                .with_label_values(&[REFRESH_POLICY_NAME, "background"])
+                .with_label_values(&[WINDOW_POLICY_NAME, "background"])
                 .observe((Instant::now() - task_start_time).as_secs_f64());
         });
 
         self.metrics
             .check_scheduling_policies_seconds
-            .with_label_values(&[REFRESH_POLICY_NAME, "main"])
+            .with_label_values(&[WINDOW_POLICY_NAME, "main"])
             .observe((Instant::now() - start_time).as_secs_f64());
     }
 
+    /// Dry-run "explain scheduling" entry point: runs every registered policy exactly as
+    /// `check_scheduling_policies` does, but returns the resulting decisions and the resolved
+    /// would-be replication factor to the caller (for a `SHOW`/`EXPLAIN` SQL surface) instead of
+    /// feeding a `Message::SchedulingDecisions` into `handle_scheduling_decisions`.
+    ///
+    /// If `cluster_id` is `Some`, only that cluster is explained; otherwise every managed,
+    /// automatically-scheduled cluster is. Unlike `check_refresh_policy`/`check_window_policy`, this
+    /// queries the timestamp oracle inline rather than on a background task, so it can be invoked
+    /// synchronously for a single cluster. It has no side effects (it does not seed cluster activity
+    /// timestamps).
+    pub(crate) async fn explain_scheduling_decisions(
+        &self,
+        cluster_id: Option<ClusterId>,
+    ) -> Vec<ExplainSchedulingDecision> {
+        // Collect the policy inputs that don't need the oracle up front.
+        let refresh_mv_write_frontiers = self.collect_refresh_mv_write_frontiers(cluster_id);
+        let mut cluster_windows = Vec::new();
+        let mut idle_configs = Vec::new();
+        let mut dependency_configs = Vec::new();
+        for cluster in self.catalog().clusters() {
+            if cluster_id.is_some_and(|filter| filter != cluster.id) {
+                continue;
+            }
+            if let ClusterVariant::Managed(ref config) = cluster.config.variant {
+                match config.schedule {
+                    ClusterSchedule::Idle { suspend_after } => {
+                        idle_configs.push((cluster.id, suspend_after));
+                    }
+                    ClusterSchedule::Window { ref window } => {
+                        cluster_windows.push((cluster.id, window.clone()));
+                    }
+                    ClusterSchedule::Dependency {
+                        depends_on,
+                        relation,
+                    } => {
+                        dependency_configs.push((cluster.id, depends_on, relation));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Query the oracle once; both the refresh and window policies read the local read ts.
+        let local_read_ts = self.get_local_timestamp_oracle().read_ts().await;
+        let now_wall_clock = mz_ore::now::to_datetime(local_read_ts.into());
+        let now = Instant::now();
+
+        // Build the same per-cluster, per-policy decision map that `handle_scheduling_decisions`
+        // accumulates, so we can reuse `resolve_desired_replication_factors` verbatim and thus run
+        // *every* registered policy (including the dependency policy) rather than a subset.
+        let mut per_cluster: BTreeMap<ClusterId, BTreeMap<&'static str, SchedulingDecision>> =
+            BTreeMap::new();
+        for (cluster_id, decision) in
+            Self::refresh_decisions(refresh_mv_write_frontiers, local_read_ts)
+        {
+            per_cluster
+                .entry(cluster_id)
+                .or_default()
+                .insert(REFRESH_POLICY_NAME, decision);
+        }
+        for (cluster_id, suspend_after) in idle_configs {
+            // Read-only: fall back to `now` when we have never recorded activity, without seeding.
+            let last_activity = self
+                .cluster_last_activity
+                .get(&cluster_id)
+                .copied()
+                .unwrap_or(now);
+            let cluster_on = now.saturating_duration_since(last_activity) < suspend_after;
+            per_cluster.entry(cluster_id).or_default().insert(
+                IDLE_POLICY_NAME,
+                SchedulingDecision::Idle(IdleDecision {
+                    cluster_on,
+                    last_activity,
+                    suspend_after,
+                }),
+            );
+        }
+        for (cluster_id, window) in cluster_windows {
+            let (cluster_on, active_window, next_transition) = window.evaluate(now_wall_clock);
+            per_cluster.entry(cluster_id).or_default().insert(
+                WINDOW_POLICY_NAME,
+                SchedulingDecision::Window(WindowDecision {
+                    cluster_on,
+                    active_window,
+                    next_transition,
+                }),
+            );
+        }
+        for (cluster_id, depends_on, relation) in dependency_configs {
+            // Provisional decision against the target's current effective state, mirroring
+            // `check_dependency_policy`; `resolve_desired_replication_factors` re-evaluates it
+            // against the target's freshly-resolved state below.
+            let target_on = self
+                .get_managed_cluster_config(depends_on)
+                .map(|config| config.replication_factor > 0)
+                .unwrap_or(false);
+            let cluster_on = relation.apply(target_on);
+            per_cluster.entry(cluster_id).or_default().insert(
+                DEPENDENCY_POLICY_NAME,
+                SchedulingDecision::Dependency(DependencyDecision {
+                    cluster_on,
+                    depends_on,
+                    relation,
+                }),
+            );
+        }
+
+        // Resolve the would-be replication factor exactly as the acting path does, so the explained
+        // factor reflects inter-cluster colocation/anti-colocation rather than the provisional
+        // per-policy opinion.
+        let resolved = self.resolve_desired_replication_factors(&per_cluster);
+
+        per_cluster
+            .into_iter()
+            .map(|(cluster_id, decisions)| {
+                let desired_replication_factor = resolved.get(&cluster_id).copied().unwrap_or_else(
+                    || {
+                        decisions
+                            .values()
+                            .map(|decision| decision.desired_replication_factor())
+                            .max()
+                            .unwrap_or(0)
+                    },
+                );
+                ExplainSchedulingDecision {
+                    cluster_id,
+                    decisions: decisions.into_values().collect(),
+                    desired_replication_factor,
+                }
+            })
+            .collect()
+    }
+
     /// Handles `SchedulingDecisions`:
     /// 1. Adds the newly made decisions to `cluster_scheduling_decisions`.
     /// 2. Cleans up old decisions that are for clusters no longer in scope of automated scheduling
     ///   decisions.
-    /// 3. For each cluster, it sums up `cluster_scheduling_decisions`, checks the summed up decision
-    ///   against the cluster state, and turns cluster On/Off if needed.
+    /// 3. For each cluster, it resolves the desired replication factor from
+    ///   `cluster_scheduling_decisions` (the factor asked for by the policy of the cluster's
+    ///   schedule, with `0` meaning Off; see `resolve_desired_replication_factors`), checks it
+    ///   against the cluster state, and turns the cluster On/Off if needed.
     #[mz_ore::instrument(level = "debug")]
     pub(crate) async fn handle_scheduling_decisions(
         &mut self,
@@ -269,29 +820,66 @@ impl Coordinator {
             }
         }
 
-        // 3. Act on `scheduling_decisions` where needed.
+        // 3. Resolve the desired replication factor for every cluster whose decisions are fully
+        // available, resolving inter-cluster dependencies in a single topological pass. We touch a
+        // cluster only when the policy of its schedule has made a decision about it. This ensures
+        // that after an envd restart that policy has a chance to run at least once before we turn
+        // off a cluster, to avoid spuriously turning off a cluster and possibly losing a hydrated
+        // state. A dependency target whose own policy has not decided yet is not waited for: its
+        // current effective On state is used.
+        let decisions_snapshot = self.cluster_scheduling_decisions.clone();
+        let resolved_desired = self.resolve_desired_replication_factors(&decisions_snapshot);
+
+        // 4. Act on the resolved decisions where needed.
         let mut altered_a_cluster = false;
-        for (cluster_id, decisions) in self.cluster_scheduling_decisions.clone() {
-            // We touch a cluster only when all policies have made a decision about it. This is
-            // to ensure that after an envd restart all policies have a chance to run at least once
-            // before we turn off a cluster, to avoid spuriously turning off a cluster and possibly
-            // losing a hydrated state.
-            if POLICIES.iter().all(|policy| decisions.contains_key(policy)) {
-                // Check whether the cluster's state matches the needed state.
-                // If any policy says On, then we need a replica.
-                let needs_replica = decisions
-                    .values()
-                    .map(|decision| decision.cluster_on())
-                    .contains(&true);
+        for (cluster_id, decisions) in decisions_snapshot {
+            if let Some(&desired_replication_factor) = resolved_desired.get(&cluster_id) {
                 let cluster_config = self
                     .get_managed_cluster_config(cluster_id)
                     .expect("cleaned up non-existing and unmanaged clusters above");
-                let has_replica = cluster_config.replication_factor > 0; // Is it On?
-                if needs_replica != has_replica {
-                    // Turn the cluster On or Off.
+                let current_replication_factor = cluster_config.replication_factor;
+                // Flap-dampening: when a decision flips the cluster between On and Off, bias toward
+                // the current state if we transitioned into it too recently. This resists both
+                // premature shutdown (tearing down a just-started cluster, losing hydrated state)
+                // and rapid restart (churning a just-suspended cluster). Scale changes that keep the
+                // cluster On (e.g. 1 <-> 2 replicas) are not dampened.
+                let currently_on = current_replication_factor > 0;
+                let desired_on = desired_replication_factor > 0;
+                if currently_on != desired_on {
+                    if let Some(last_transition) =
+                        self.cluster_scheduling_last_transition.get(&cluster_id)
+                    {
+                        let since_transition = last_transition.elapsed();
+                        if currently_on && since_transition < cluster_config.min_uptime {
+                            debug!(
+                                "handle_scheduling_decisions: \
+                                Not turning cluster {} Off: it was turned On {:?} ago, \
+                                which is less than MIN UPTIME {:?}",
+                                cluster_id, since_transition, cluster_config.min_uptime,
+                            );
+                            continue;
+                        }
+                        if !currently_on && since_transition < cluster_config.cooldown {
+                            debug!(
+                                "handle_scheduling_decisions: \
+                                Not turning cluster {} On: it was turned Off {:?} ago, \
+                                which is less than COOLDOWN {:?}",
+                                cluster_id, since_transition, cluster_config.cooldown,
+                            );
+                            continue;
+                        }
+                    }
+                }
+                if desired_replication_factor != current_replication_factor {
+                    // Scale the cluster up or down (including all the way Off).
                     altered_a_cluster = true;
+                    if currently_on != desired_on {
+                        // Record the On/Off transition time for flap-dampening.
+                        self.cluster_scheduling_last_transition
+                            .insert(cluster_id, Instant::now());
+                    }
                     let mut new_config = cluster_config.clone();
-                    new_config.replication_factor = if needs_replica { 1 } else { 0 };
+                    new_config.replication_factor = desired_replication_factor;
                     if let Err(e) = self
                         .sequence_alter_cluster_managed_to_managed(
                             None,
@@ -319,7 +907,8 @@ impl Coordinator {
             } else {
                 debug!(
                     "handle_scheduling_decisions: \
-                    Not all policies have made a decision about cluster {}. decisions: {:?}",
+                    The policy of cluster {}'s schedule has not made a decision about it \
+                    yet (or it waits on a dependency target). decisions: {:?}",
                     cluster_id, decisions,
                 );
             }
@@ -331,6 +920,126 @@ impl Coordinator {
             .observe((Instant::now() - start_time).as_secs_f64());
     }
 
+    /// Resolves the desired replication factor for every cluster whose decisions are fully
+    /// available, taking inter-cluster dependencies into account.
+    ///
+    /// A cluster is only resolved once the policy of its schedule has decided about it *and* every
+    /// cluster it depends on has itself been resolved (or is not a candidate, i.e. is not
+    /// automatically scheduled or its policy has not decided yet, in which case its current
+    /// effective On state is used). Dependency decisions are re-evaluated against
+    /// the target's freshly-resolved On state rather than the provisional state the dependency
+    /// policy computed, so colocation/anti-colocation reflect this round's decisions. The graph is
+    /// walked in a single topological pass (a fixpoint over the ready set); a dependency cycle is
+    /// reported via `soft_panic_or_log!` and the offending clusters fall back to their provisional
+    /// decisions instead of looping forever.
+    fn resolve_desired_replication_factors(
+        &self,
+        decisions: &BTreeMap<ClusterId, BTreeMap<&'static str, SchedulingDecision>>,
+    ) -> BTreeMap<ClusterId, u32> {
+        // A managed cluster holds exactly one `ClusterSchedule` variant, so exactly one policy
+        // ever opines about it. A cluster is a candidate once that policy has decided about it, and
+        // we resolve it using *only* that policy's decision. This both makes the gate satisfiable
+        // (requiring every registered policy to decide would be impossible: an `ON REFRESH` cluster
+        // never gets an `"idle"`/`"window"`/`"dependency"` decision, and vice versa) and ignores any
+        // stale decision left behind by a previous `SCHEDULE` variant after an `ALTER CLUSTER`,
+        // which step 2 does not evict per-policy.
+        let candidates: BTreeMap<ClusterId, &SchedulingDecision> = decisions
+            .iter()
+            .filter_map(|(cluster_id, cluster_decisions)| {
+                let policy = Self::policy_for_schedule(
+                    &self.get_managed_cluster_config(*cluster_id)?.schedule,
+                )?;
+                Some((*cluster_id, cluster_decisions.get(policy)?))
+            })
+            .collect();
+
+        // The On state a dependency's target resolves to: `Some(on)` if resolvable now, `None` if we
+        // must wait for the target (a candidate that hasn't resolved yet).
+        let target_on = |resolved: &BTreeMap<ClusterId, u32>, target: ClusterId| -> Option<bool> {
+            if let Some(factor) = resolved.get(&target) {
+                Some(*factor > 0)
+            } else if candidates.contains_key(&target) {
+                // Target is under scheduling but not resolved yet; defer.
+                None
+            } else {
+                // Target is not automatically scheduled, or its policy has not decided yet; use its
+                // current effective state.
+                Some(
+                    self.get_managed_cluster_config(target)
+                        .map(|config| config.replication_factor > 0)
+                        .unwrap_or(false),
+                )
+            }
+        };
+
+        // Computes a candidate's desired replication factor from its single applicable decision, or
+        // `None` if it still waits on a dependency target.
+        let compute = |resolved: &BTreeMap<ClusterId, u32>,
+                       decision: &SchedulingDecision|
+         -> Option<u32> {
+            match decision {
+                SchedulingDecision::Dependency(DependencyDecision {
+                    depends_on,
+                    relation,
+                    ..
+                }) => {
+                    let on = relation.apply(target_on(resolved, *depends_on)?);
+                    Some(if on { 1 } else { 0 })
+                }
+                other => Some(other.desired_replication_factor()),
+            }
+        };
+
+        let mut resolved: BTreeMap<ClusterId, u32> = BTreeMap::new();
+        // Fixpoint: resolve candidates whose dependencies are resolved until no progress is made.
+        loop {
+            let mut progressed = false;
+            for (cluster_id, &decision) in &candidates {
+                if resolved.contains_key(cluster_id) {
+                    continue;
+                }
+                if let Some(desired) = compute(&resolved, decision) {
+                    resolved.insert(*cluster_id, desired);
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+
+        // Any still-unresolved candidates form a dependency cycle. Break it by falling back to each
+        // cluster's provisional dependency decision (computed against the target's current state).
+        for (cluster_id, &decision) in &candidates {
+            if resolved.contains_key(cluster_id) {
+                continue;
+            }
+            soft_panic_or_log!(
+                "resolve_desired_replication_factors: dependency cycle involving cluster {}; \
+                 falling back to provisional decisions",
+                cluster_id
+            );
+            // Fall back to the provisional dependency decision (computed against the target's
+            // current effective state by `check_dependency_policy`).
+            resolved.insert(*cluster_id, decision.desired_replication_factor());
+        }
+
+        resolved
+    }
+
+    /// The scheduling policy that applies to a managed cluster with the given schedule, if any. A
+    /// managed cluster holds exactly one `ClusterSchedule` variant, so at most one policy ever
+    /// opines about it; `SCHEDULE = MANUAL` clusters are not automatically scheduled at all.
+    fn policy_for_schedule(schedule: &ClusterSchedule) -> Option<&'static str> {
+        match schedule {
+            ClusterSchedule::Manual => None,
+            ClusterSchedule::Refresh { .. } => Some(REFRESH_POLICY_NAME),
+            ClusterSchedule::Idle { .. } => Some(IDLE_POLICY_NAME),
+            ClusterSchedule::Window { .. } => Some(WINDOW_POLICY_NAME),
+            ClusterSchedule::Dependency { .. } => Some(DEPENDENCY_POLICY_NAME),
+        }
+    }
+
     /// Returns the managed config for a cluster. Returns None if the cluster doesn't exist or if
     /// it's an unmanaged cluster.
     fn get_managed_cluster_config(&self, cluster_id: ClusterId) -> Option<ClusterVariantManaged> {